@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use crate::tokeniser::{Token, TokenKind};
+use crate::error::CompileError;
+
+/* The AST is built from the RPN produced by `to_rpn` using the same stack
+ * discipline as `sema_rpn`: operands push a leaf, operators pop their
+ * sub-trees and push the resulting node. */
+#[derive(Debug, Clone, PartialEq)]
+pub enum AstNode {
+    Num(i64),
+    Var(Vec<u8>),
+    BinOp { op: TokenKind, lhs: Box<AstNode>, rhs: Box<AstNode>, line: u32, col: u32 },
+    Assign { name: Vec<u8>, value: Box<AstNode> },
+}
+
+fn pop_operand(stack: &mut Vec<AstNode>, e: &Token) -> Result<AstNode, CompileError> {
+    stack.pop().ok_or_else(|| CompileError::StackUnderflow {
+        op: e.to_string().to_owned(), line: e.line(), col: e.col()
+    })
+}
+
+pub fn to_ast(expr: Vec<Token>) -> Result<AstNode, CompileError> {
+    let mut stack: Vec<AstNode> = Vec::new();
+
+    for e in expr {
+        match e.kind() {
+            TokenKind::Num(n) => stack.push(AstNode::Num(*n)),
+            TokenKind::Ident(name) => stack.push(AstNode::Var(name.clone())),
+
+            TokenKind::Assign => {
+                let value = pop_operand(&mut stack, &e)?;
+                let target = pop_operand(&mut stack, &e)?;
+
+                let name = match target {
+                    AstNode::Var(name) => name,
+                    _ => return Err(CompileError::ExpectedOperand {
+                        after: e.to_string().to_owned(), line: e.line(), col: e.col()
+                    })
+                };
+
+                stack.push(AstNode::Assign { name, value: Box::new(value) });
+            }
+
+            _ => {
+                let rhs = pop_operand(&mut stack, &e)?;
+                let lhs = pop_operand(&mut stack, &e)?;
+                stack.push(AstNode::BinOp {
+                    op: e.kind().clone(), lhs: Box::new(lhs), rhs: Box::new(rhs),
+                    line: e.line(), col: e.col()
+                });
+            }
+        }
+    }
+
+    stack.pop().ok_or(CompileError::StackUnderflow {
+        op: "<expression>".to_owned(), line: 0, col: 0
+    })
+}
+
+pub fn eval(node: &AstNode, env: &mut HashMap<Vec<u8>, i64>) -> Result<i64, CompileError> {
+    match node {
+        AstNode::Num(n) => Ok(*n),
+
+        AstNode::Var(name) => env.get(name).copied().ok_or_else(||
+            CompileError::UndefinedVariable {
+                name: String::from_utf8_lossy(name).into_owned()
+            }),
+
+        AstNode::Assign { name, value } => {
+            let v = eval(value, env)?;
+            env.insert(name.clone(), v);
+            Ok(v)
+        }
+
+        AstNode::BinOp { op, lhs, rhs, line, col } => {
+            let l = eval(lhs, env)?;
+            let r = eval(rhs, env)?;
+
+            let overflow = |op: &str| CompileError::IntegerOverflow {
+                op: op.to_owned(), line: *line, col: *col
+            };
+
+            match op {
+                TokenKind::Add => l.checked_add(r).ok_or_else(|| overflow("+")),
+                TokenKind::Sub => l.checked_sub(r).ok_or_else(|| overflow("-")),
+                TokenKind::Mul => l.checked_mul(r).ok_or_else(|| overflow("*")),
+
+                TokenKind::Div => {
+                    if r == 0 {
+                        return Err(CompileError::DivisionByZero { line: *line, col: *col });
+                    }
+                    l.checked_div(r).ok_or_else(|| overflow("/"))
+                }
+
+                TokenKind::Mod => {
+                    if r == 0 {
+                        return Err(CompileError::DivisionByZero { line: *line, col: *col });
+                    }
+                    l.checked_rem(r).ok_or_else(|| overflow("%"))
+                }
+
+                TokenKind::And => Ok(l & r),
+                TokenKind::Or  => Ok(l | r),
+                TokenKind::Xor => Ok(l ^ r),
+
+                TokenKind::Eq => Ok((l == r) as i64),
+                TokenKind::Ne => Ok((l != r) as i64),
+                TokenKind::Lt => Ok((l <  r) as i64),
+                TokenKind::Le => Ok((l <= r) as i64),
+                TokenKind::Gt => Ok((l >  r) as i64),
+                TokenKind::Ge => Ok((l >= r) as i64),
+                _ => unreachable!()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokeniser::Tokeniser;
+    use crate::{expr_parser, sema};
+
+    fn eval_expr(src: &str) -> Result<i64, CompileError> {
+        let mut tokeniser = Tokeniser::new(src.to_string())?;
+        let parsed = tokeniser.collect()?;
+        sema::sema_infix(&parsed)?;
+        let rpn = expr_parser::to_rpn(parsed)?;
+        sema::sema_rpn(&rpn)?;
+        let tree = to_ast(rpn)?;
+        let mut env = HashMap::new();
+        eval(&tree, &mut env)
+    }
+
+    #[test]
+    fn division_by_zero_is_an_error() {
+        assert!(matches!(eval_expr("5 / 0"), Err(CompileError::DivisionByZero { .. })));
+    }
+
+    #[test]
+    fn modulo_by_zero_is_an_error() {
+        assert!(matches!(eval_expr("5 % 0"), Err(CompileError::DivisionByZero { .. })));
+    }
+
+    #[test]
+    fn multiplication_overflow_is_an_error() {
+        assert!(matches!(
+            eval_expr("9999999999 * 9999999999"),
+            Err(CompileError::IntegerOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn ordinary_arithmetic_does_not_overflow() {
+        assert_eq!(eval_expr("3 + 4 * 2").unwrap(), 11);
+    }
+
+    #[test]
+    fn relational_and_equality_operators_yield_one_or_zero() {
+        assert_eq!(eval_expr("1 == 1").unwrap(), 1);
+        assert_eq!(eval_expr("1 != 1").unwrap(), 0);
+        assert_eq!(eval_expr("1 < 2").unwrap(), 1);
+        assert_eq!(eval_expr("2 <= 2").unwrap(), 1);
+        assert_eq!(eval_expr("3 > 2").unwrap(), 1);
+        assert_eq!(eval_expr("2 >= 3").unwrap(), 0);
+    }
+
+    #[test]
+    fn comparisons_can_be_combined_with_bitwise_operators() {
+        assert_eq!(eval_expr("(1 == 1) & 1").unwrap(), 1);
+    }
+}
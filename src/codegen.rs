@@ -0,0 +1,88 @@
+use std::io::Write;
+
+use crate::tokeniser::{Token, TokenKind};
+use crate::error::CompileError;
+
+fn io_err(e: std::io::Error) -> CompileError {
+    CompileError::IoError { message: e.to_string() }
+}
+
+fn underflow(e: &Token) -> CompileError {
+    CompileError::StackUnderflow { op: e.to_string().to_owned(), line: e.line(), col: e.col() }
+}
+
+// A value still sitting in a named variable is left unmaterialised so an
+// assignment target can be turned into a `store` instead of a redundant
+// `load` followed by a `store`.
+enum Val {
+    Id(u32),
+    Var(Vec<u8>)
+}
+
+fn materialise(v: Val, out: &mut dyn Write, id: &mut u32) -> Result<u32, CompileError> {
+    match v {
+        Val::Id(n) => Ok(n),
+        Val::Var(name) => {
+            let this = *id;
+            *id += 1;
+            writeln!(out, "%{} = load i64 {}", this, String::from_utf8_lossy(&name))
+                .map_err(io_err)?;
+            Ok(this)
+        }
+    }
+}
+
+/* Emits SSA-form three-address IR for the RPN produced by `to_rpn`,
+ * tracking value numbering with a counter and a stack of values, and
+ * routing failures (operand underflow, a non-variable assignment target,
+ * write errors) through `CompileError`. */
+pub fn generate(expr: &[Token], out: &mut dyn Write) -> Result<(), CompileError> {
+    let mut id = 0u32;
+    let mut stack: Vec<Val> = Vec::new();
+
+    for e in expr {
+        match e.kind() {
+            TokenKind::Num(n) => {
+                let this = id;
+                id += 1;
+                writeln!(out, "%{} = load i64 {}", this, n).map_err(io_err)?;
+                stack.push(Val::Id(this));
+            }
+
+            TokenKind::Ident(name) => stack.push(Val::Var(name.clone())),
+
+            TokenKind::Assign => {
+                let value = stack.pop().ok_or_else(|| underflow(e))?;
+                let target = stack.pop().ok_or_else(|| underflow(e))?;
+
+                let name = match target {
+                    Val::Var(name) => name,
+                    Val::Id(_) => return Err(CompileError::ExpectedOperand {
+                        after: e.to_string().to_owned(), line: e.line(), col: e.col()
+                    })
+                };
+
+                let value_id = materialise(value, out, &mut id)?;
+                writeln!(out, "store i64 %{}, {}", value_id, String::from_utf8_lossy(&name))
+                    .map_err(io_err)?;
+                stack.push(Val::Id(value_id));
+            }
+
+            _ => {
+                let rhs = stack.pop().ok_or_else(|| underflow(e))?;
+                let lhs = stack.pop().ok_or_else(|| underflow(e))?;
+
+                let lhs_id = materialise(lhs, out, &mut id)?;
+                let rhs_id = materialise(rhs, out, &mut id)?;
+
+                let this = id;
+                id += 1;
+                writeln!(out, "%{} = {} i64 %{}, %{}", this, e.to_string(), lhs_id, rhs_id)
+                    .map_err(io_err)?;
+                stack.push(Val::Id(this));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+
+/* Centralised diagnostic type for every compilation pass. Variants tied to
+ * a source position carry the line/column of the offending token (NonAscii
+ * carries only a character offset; UndefinedVariable and IoError carry no
+ * position) so `Display` can render the message in one place instead of
+ * every pass printing its own. */
+#[derive(Debug, PartialEq)]
+pub enum CompileError {
+    InvalidToken { token: u8, line: u32, col: u32 },
+    MismatchedParen { paren: char, line: u32, col: u32 },
+    ExpectedOperator { after: String, line: u32, col: u32 },
+    ExpectedOperand { after: String, line: u32, col: u32 },
+    StackUnderflow { op: String, line: u32, col: u32 },
+    NonAscii { col: u32 },
+    UndefinedVariable { name: String },
+    Redefinition { name: String, line: u32, col: u32 },
+    DivisionByZero { line: u32, col: u32 },
+    IntegerOverflow { op: String, line: u32, col: u32 },
+    IoError { message: String },
+    DefineOverflow { name: String, line: u32, col: u32 },
+    UnsupportedDefine { name: String, value: String, line: u32, col: u32 },
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompileError::InvalidToken { token, line, col } =>
+                write!(f, "Invalid token {} at line {} column {}",
+                    *token as char, line, col),
+
+            CompileError::MismatchedParen { paren, line, col } =>
+                write!(f, "Mismatched parenthesis {} at line {} column {}",
+                    paren, line, col),
+
+            CompileError::ExpectedOperator { after, line, col } =>
+                write!(f, "Expected operator after '{}' at line {} column {}",
+                    after, line, col),
+
+            CompileError::ExpectedOperand { after, line, col } =>
+                write!(f, "Expected operand after '{}' at line {} column {}",
+                    after, line, col),
+
+            CompileError::StackUnderflow { op, line, col } =>
+                write!(f, "Operator {} at line {} column {} does not have enough operands",
+                    op, line, col),
+
+            CompileError::NonAscii { col } =>
+                write!(f, "Non ASCII character found at offset {}", col),
+
+            CompileError::UndefinedVariable { name } =>
+                write!(f, "Undefined variable '{}'", name),
+
+            CompileError::Redefinition { name, line, col } =>
+                write!(f, "Redefinition of '{}' at line {} column {}", name, line, col),
+
+            CompileError::DivisionByZero { line, col } =>
+                write!(f, "Division by zero at line {} column {}", line, col),
+
+            CompileError::IntegerOverflow { op, line, col } =>
+                write!(f, "Integer overflow in '{}' at line {} column {}", op, line, col),
+
+            CompileError::IoError { message } =>
+                write!(f, "I/O error: {}", message),
+
+            CompileError::DefineOverflow { name, line, col } =>
+                write!(f, "Value of '#define {}' does not fit in an i64 at line {} column {}",
+                    name, line, col),
+
+            CompileError::UnsupportedDefine { name, value, line, col } =>
+                write!(f, "'#define {} {}' at line {} column {} is not a single (optionally \
+                    signed) integer literal, which is all this preprocessor can substitute",
+                    name, value, line, col),
+        }
+    }
+}
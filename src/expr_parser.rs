@@ -1,5 +1,6 @@
 use crate::tokeniser::Token;
 use crate::tokeniser::TokenKind;
+use crate::error::CompileError;
 
 // https://en.wikipedia.org/wiki/Shunting_yard_algorithm
 // The below two functions implement the shunting yard algorithm
@@ -33,7 +34,7 @@ pub fn add_operator(opstack: &mut Vec<Token>, output: &mut Vec<Token>, op: Token
     opstack.push(op);
 }
 
-pub fn to_rpn(expr: Vec<Token>) -> Vec<Token> {
+pub fn to_rpn(expr: Vec<Token>) -> Result<Vec<Token>, CompileError> {
     let mut ret: Vec<Token> = Vec::new();
     let mut opstack: Vec<Token> = Vec::new();
 
@@ -51,8 +52,9 @@ pub fn to_rpn(expr: Vec<Token>) -> Vec<Token> {
                     ret.push(op);
                 }
 
-                panic!("Mismatched parenthesis at line {} column {}", 
-                        e.line(), e.col());
+                return Err(CompileError::MismatchedParen {
+                    paren: ')', line: e.line(), col: e.col()
+                });
             }
             _ => add_operator(&mut opstack, &mut ret, e)
         };
@@ -62,6 +64,6 @@ pub fn to_rpn(expr: Vec<Token>) -> Vec<Token> {
         ret.push(opstack.pop().unwrap());
     }
 
-    ret
+    Ok(ret)
 }
 
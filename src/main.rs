@@ -1,4 +1,7 @@
+use std::collections::HashMap;
 use std::env;
+use std::fs::File;
+use std::io;
 use std::process;
 
 mod expr_parser;
@@ -6,63 +9,69 @@ mod tokeniser;
 mod robuffer;
 mod utils;
 mod sema;
+mod error;
+mod ast;
+mod preprocessor;
+mod codegen;
 
 use crate::tokeniser::{Tokeniser, Token};
+use crate::error::CompileError;
 
-/*
-use std::io::Write;
-fn code_dump(expr: Vec<Token>) -> std::io::Result<()> {
-    use std::fs::File;
-    let mut file = File::create("compile.ir")?;
-    let mut id = 0u32;
-    let mut stack: Vec<u32> = Vec::new();
-    for e in expr {
-        match e.kind() {
-            TokenKind::Num(x) => {
-                stack.push(id);
-                writeln!(&mut file, "%{} = load i64 {}", id, x).unwrap();
-                id += 1;
-            }
+fn run(expr: String, emit: Option<&str>) -> Result<i64, CompileError> {
+    let mut tokeniser = Tokeniser::new(expr)?;
+    let parsed: Vec<Token> = tokeniser.collect()?;
+    sema::sema_infix(&parsed)?;
 
-            _ => {
-                let e1 = stack.pop().unwrap(); // b
-                let e2 = stack.pop().unwrap(); // a
-                // Operation format: [OP_NAME] a, b
-                writeln!(&mut file, "%{} = {} i64 %{}, %{}", id, e.to_string(), 
-                    e2, e1).unwrap();
+    let rpn: Vec<Token> = expr_parser::to_rpn(parsed)?;
+    sema::sema_rpn(&rpn)?;
 
-                stack.push(id);  
-                id += 1;
-            }
-        };
-    }
+    println!("RPN Expression = {:?}", rpn);
 
-    Ok(())
-} */
+    match emit {
+        Some(path) => {
+            let mut file = File::create(path)
+                .map_err(|e| CompileError::IoError { message: e.to_string() })?;
+            codegen::generate(&rpn, &mut file)?;
+        }
 
-fn main() {
-    let mut args = env::args();
-    if args.len() != 2 {
-        println!("Usage: {} [EXPR]", args.nth(0).unwrap());
-        process::exit(1);
+        None => {
+            let stdout = io::stdout();
+            let mut handle = stdout.lock();
+            codegen::generate(&rpn, &mut handle)?;
+        }
     }
 
-    let expr = args.nth(1).unwrap();
-    let mut tokeniser = Tokeniser::new(expr);
-    let parsed: Result<Vec<Token>, ()> = tokeniser.collect();
-    if parsed.is_err() {
-        process::exit(1);
-    }
+    let tree = ast::to_ast(rpn)?;
+    let mut env: HashMap<Vec<u8>, i64> = HashMap::new();
+    ast::eval(&tree, &mut env)
+}
 
-    if !sema::sema_infix(parsed.as_ref().unwrap()) {
-        process::exit(1);
-    }
+fn usage(prog: &str) -> ! {
+    println!("Usage: {} [EXPR] [-o FILE]", prog);
+    process::exit(1);
+}
 
-    let rpn: Vec<Token> = expr_parser::to_rpn(parsed.unwrap());
-    if !sema::sema_rpn(&rpn) {
-        process::exit(1);
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 && args.len() != 4 {
+        usage(&args[0]);
     }
 
-    println!("RPN Expression = {:?}", rpn);
-    //code_dump(rpn).unwrap();
+    let expr = args[1].clone();
+    let emit_path = if args.len() == 4 {
+        if args[2] != "-o" {
+            usage(&args[0]);
+        }
+        Some(args[3].as_str())
+    } else {
+        None
+    };
+
+    match run(expr, emit_path) {
+        Ok(result) => println!("Result = {}", result),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
 }
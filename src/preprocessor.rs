@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use crate::error::CompileError;
+use crate::utils::is_digit;
+
+fn is_integer_literal(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let digits = match bytes.first() {
+        Some(b'-') | Some(b'+') => &bytes[1..],
+        _ => bytes
+    };
+
+    !digits.is_empty() && digits.iter().all(|b| is_digit(*b))
+}
+
+// The line a macro was defined on, alongside its value. The tokeniser only
+// honours a substitution for identifiers occurring *after* this line, so a
+// use of `NAME` preceding its `#define` is left as an ordinary identifier
+// rather than being spliced in.
+pub type Define = (Vec<u8>, u32);
+
+/* Strips `#define NAME VALUE` directives out of the source before it
+ * reaches the tokeniser. Each directive line is
+ * replaced by a blank line so downstream line numbers stay accurate.
+ * VALUE must be a single (optionally signed) integer literal, since that
+ * is all the tokeniser's identifier substitution knows how to splice in;
+ * it is read as decimal, so a leading 0 does not make it octal.
+ * Substitution only ever applies to the remainder of the input that
+ * follows a directive; the definition's line number travels with it so
+ * the tokeniser can enforce that ordering. */
+pub fn preprocess(src: &str) -> Result<(String, HashMap<Vec<u8>, Define>), CompileError> {
+    let mut defines: HashMap<Vec<u8>, Define> = HashMap::new();
+    let mut out = String::new();
+
+    for (idx, line) in src.lines().enumerate() {
+        let ln = idx as u32 + 1;
+        let directive_col = (line.len() - line.trim_start().len()) as u32 + 1;
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("#define") {
+            let after_kw = &trimmed["#define".len()..];
+            let name_col = directive_col + "#define".len() as u32
+                + (after_kw.len() - after_kw.trim_start().len()) as u32;
+
+            let rest = after_kw.trim();
+            let mut parts = rest.splitn(2, char::is_whitespace);
+            let name = parts.next().unwrap_or("").trim();
+            let value = parts.next().unwrap_or("").trim();
+
+            if name.is_empty() {
+                return Err(CompileError::ExpectedOperand {
+                    after: "#define".to_owned(), line: ln, col: directive_col
+                });
+            }
+
+            if !is_integer_literal(value) {
+                return Err(CompileError::UnsupportedDefine {
+                    name: name.to_owned(), value: value.to_owned(), line: ln, col: name_col
+                });
+            }
+
+            let name_bytes = name.as_bytes().to_vec();
+            let value_bytes = value.as_bytes().to_vec();
+
+            if let Some((prev, _)) = defines.get(&name_bytes) {
+                if prev != &value_bytes {
+                    return Err(CompileError::Redefinition {
+                        name: name.to_owned(), line: ln, col: name_col
+                    });
+                }
+            }
+
+            defines.insert(name_bytes, (value_bytes, ln));
+            out.push('\n');
+            continue;
+        }
+
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    Ok((out, defines))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_directive_and_records_value_with_its_line() {
+        let (out, defines) = preprocess("#define WIDTH 80\n5 + WIDTH").unwrap();
+        assert_eq!(out, "\n5 + WIDTH\n");
+        assert_eq!(defines.get(b"WIDTH".as_slice()), Some(&(b"80".to_vec(), 1)));
+    }
+
+    #[test]
+    fn accepts_a_negative_integer_literal() {
+        let (_, defines) = preprocess("#define NEG -5\n").unwrap();
+        assert_eq!(defines.get(b"NEG".as_slice()), Some(&(b"-5".to_vec(), 1)));
+    }
+
+    #[test]
+    fn rejects_a_multi_token_value() {
+        assert!(preprocess("#define FOO 1 + 2\n").is_err());
+    }
+
+    #[test]
+    fn rejects_a_non_numeric_value() {
+        assert!(preprocess("#define FOO bar\n").is_err());
+    }
+
+    #[test]
+    fn allows_identical_redefinition() {
+        assert!(preprocess("#define A 1\n#define A 1\n").is_ok());
+    }
+
+    #[test]
+    fn rejects_conflicting_redefinition() {
+        assert!(preprocess("#define A 1\n#define A 2\n").is_err());
+    }
+
+    #[test]
+    fn rejects_missing_name() {
+        assert!(preprocess("#define\n").is_err());
+    }
+}
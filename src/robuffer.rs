@@ -1,3 +1,5 @@
+use crate::error::CompileError;
+
 #[derive(Debug)]
 // Read-only buffer
 pub struct ROBuffer {
@@ -6,13 +8,13 @@ pub struct ROBuffer {
 }
 
 impl ROBuffer {
-    pub fn new(ty: String) -> Result<Self, ()> {
+    pub fn new(ty: String) -> Result<Self, CompileError> {
         let mut bytes: Vec<u8> = Vec::new();
         let chars: Vec<char> = ty.chars().collect();
-        for ch in chars {
+        for (idx, ch) in chars.into_iter().enumerate() {
             let b = u32::from(ch);
             if b > 0x7F {
-                return Err(())
+                return Err(CompileError::NonAscii { col: idx as u32 + 1 })
             }
 
             bytes.push(b as u8)
@@ -1,4 +1,5 @@
 use crate::tokeniser::{Token, TokenKind};
+use crate::error::CompileError;
 
 /* Infix expressions are validated against the following rules
  * 1) No two operands (Num or Ident) should be right beside each other 
@@ -10,7 +11,7 @@ use crate::tokeniser::{Token, TokenKind};
  *  Note: The actual matching of left to right brackets is left to the 
  *  expression parser which can easily figure this out
  */
-pub fn sema_infix(expr: &Vec<Token>) -> bool {
+pub fn sema_infix(expr: &Vec<Token>) -> Result<(), CompileError> {
     // Rule 3
     let mut lpars = 0;
     let mut rpars = 0;
@@ -28,18 +29,11 @@ pub fn sema_infix(expr: &Vec<Token>) -> bool {
     }
 
     if lpars != rpars {
-        let ch: char;
+        let ch = if lpars > rpars { '(' } else { ')' };
 
-        if lpars > rpars { 
-            ch = '(';
-        }
-        else {
-            ch = ')';
-        }
-
-        println!("Extra parenthesis {} found at line {} column {}", 
-                ch, lastpar.line(), lastpar.col());
-        return false;
+        return Err(CompileError::MismatchedParen {
+            paren: ch, line: lastpar.line(), col: lastpar.col()
+        });
     }
 
     // Rules 1 and 2
@@ -53,33 +47,33 @@ pub fn sema_infix(expr: &Vec<Token>) -> bool {
             }
 
             let next_atom_uw = next_atom.unwrap();
-            if next_atom_uw.is_operand() || next_atom_uw.is_paren() {
-                println!("Expected operator after '{}' at line {} column {}",
-                        atom.value(), atom.line(), atom.col());
-                return false;
+            if next_atom_uw.is_operand() || *next_atom_uw.kind() == TokenKind::LPar {
+                return Err(CompileError::ExpectedOperator {
+                    after: atom.value(), line: atom.line(), col: atom.col()
+                });
             }
         }
 
         else if atom.is_operator() {
             let next_atom = expr.get(idx + 1);
             if next_atom.is_none() { // last element in expression
-                println!("Expected operand after '{}' at line {} column {}",
-                    atom.to_string(), atom.line(), atom.col());
-                return false;
+                return Err(CompileError::ExpectedOperand {
+                    after: atom.to_string().to_owned(), line: atom.line(), col: atom.col()
+                });
             }
 
             let next_atom_uw = next_atom.unwrap();
             if next_atom_uw.is_operator() {
-                println!("Expected operand after '{}' at line {} column {}",
-                        atom.to_string(), atom.line(), atom.col());
-                return false;
+                return Err(CompileError::ExpectedOperand {
+                    after: atom.to_string().to_owned(), line: atom.line(), col: atom.col()
+                });
             }
         }
 
         idx += 1;
     }
 
-    true
+    Ok(())
 }
 
 /* Runs expr in a "virtual type system" that is, emulates the running of
@@ -91,50 +85,71 @@ pub fn sema_infix(expr: &Vec<Token>) -> bool {
  * 1) After expr is executed one and only one value is left on the vstack
  * 2) vstack does not underflow at any point during execution
  */
-pub fn sema_rpn(expr: &Vec<Token>) -> bool {
+pub fn sema_rpn(expr: &Vec<Token>) -> Result<(), CompileError> {
     let dummy = 0u32;
     let mut vstack: Vec<u32> = Vec::new();
     for e in expr {
         match e.kind() {
             TokenKind::Num(_) | TokenKind::Ident(_) => vstack.push(dummy),
             TokenKind::LPar => {
-                println!("Extra '(' found at at line {} column {}",
-                        e.line(), e.col());
-                return false;
+                return Err(CompileError::MismatchedParen {
+                    paren: '(', line: e.line(), col: e.col()
+                });
             }
 
             TokenKind::RPar => {
-                println!("Extra ')' found at at line {} column {}",
-                        e.line(), e.col());
-                return false;
+                return Err(CompileError::MismatchedParen {
+                    paren: ')', line: e.line(), col: e.col()
+                });
             }
 
             _ => {
                 let op1 = vstack.pop();
                 let op2 = vstack.pop();
-                if op1.is_none() {
-                    println!("Operator {} at line {} column {} has no operands but needs 2",
-                            e.to_string(), e.line(), e.col());
-                    return false;
-                }
-
-                if op2.is_none() {
-                    println!("Operator {} at line {} column {} has 1 operand but needs 2",
-                            e.to_string(), e.line(), e.col());
-                    return false;
+                if op1.is_none() || op2.is_none() {
+                    return Err(CompileError::StackUnderflow {
+                        op: e.to_string().to_owned(), line: e.line(), col: e.col()
+                    });
                 }
 
-                op1.unwrap();
-                op2.unwrap();
                 vstack.push(dummy);
             }
         }
     }
 
     if vstack.len() != 1 {
-        println!("RPN generator internal error: vstack has excess elements");
-        return false;
+        return Err(CompileError::StackUnderflow {
+            op: "<expression>".to_owned(), line: 0, col: 0
+        });
     }
 
-    true
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokeniser::Tokeniser;
+
+    fn tokens(src: &str) -> Vec<Token> {
+        Tokeniser::new(src.to_string()).unwrap().collect().unwrap()
+    }
+
+    #[test]
+    fn accepts_grouped_expressions_ending_in_an_operand() {
+        assert!(sema_infix(&tokens("(1 + 2)")).is_ok());
+        assert!(sema_infix(&tokens("(1 + 2) * 3")).is_ok());
+        assert!(sema_infix(&tokens("((5))")).is_ok());
+        assert!(sema_infix(&tokens("(1 == 1) & 1")).is_ok());
+    }
+
+    #[test]
+    fn rejects_adjacent_operands() {
+        assert!(sema_infix(&tokens("1 2")).is_err());
+    }
+
+    #[test]
+    fn rejects_operand_followed_by_open_paren() {
+        assert!(sema_infix(&tokens("1 (2)")).is_err());
+    }
 }
@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+
 use crate::robuffer::ROBuffer;
 use crate::utils::{is_digit, is_alnum};
+use crate::error::CompileError;
+use crate::preprocessor::{self, Define};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenKind {
@@ -17,6 +21,12 @@ pub enum TokenKind {
     Xor, // '^'
     Or, // '|'
     Assign, // '='
+    Eq, // '=='
+    Ne, // '!='
+    Lt, // '<'
+    Le, // '<='
+    Gt, // '>'
+    Ge, // '>='
     End
 }
 
@@ -51,18 +61,13 @@ impl Token {
         }
     }
 
-    pub fn is_paren(&self) -> bool {
-        match self.kind {
-            TokenKind::LPar | TokenKind::RPar => true,
-            _ => false
-        }
-    }
-
     pub fn is_operator(&self) -> bool {
         match self.kind {
             TokenKind::Add | TokenKind::Sub | TokenKind::Mul |
             TokenKind::Div | TokenKind::And | TokenKind::Mod |
-            TokenKind::Xor | TokenKind::Or  | TokenKind::Assign => true,
+            TokenKind::Xor | TokenKind::Or  | TokenKind::Assign |
+            TokenKind::Eq  | TokenKind::Ne  | TokenKind::Lt |
+            TokenKind::Le  | TokenKind::Gt  | TokenKind::Ge => true,
             _ => false
         }
     }
@@ -87,6 +92,12 @@ impl Token {
             TokenKind::Xor => "^",
             TokenKind::Or  => "|",
             TokenKind::Assign => "=",
+            TokenKind::Eq => "==",
+            TokenKind::Ne => "!=",
+            TokenKind::Lt => "<",
+            TokenKind::Le => "<=",
+            TokenKind::Gt => ">",
+            TokenKind::Ge => ">=",
             _ => unreachable!()
         }
     }
@@ -95,6 +106,8 @@ impl Token {
         return match &self.kind() {
             TokenKind::Assign => -1, // Lowest possible priority
             TokenKind::Num(_) | TokenKind::Ident(_) => 0,
+            TokenKind::Eq | TokenKind::Ne | TokenKind::Lt |
+            TokenKind::Le | TokenKind::Gt | TokenKind::Ge => 0,
             TokenKind::Or  => 1,
             TokenKind::Xor => 2,
             TokenKind::And => 3,
@@ -113,18 +126,21 @@ impl Token {
 }
 
 pub struct Tokeniser {
-    buf:    ROBuffer,
-    line:   u32,
-    column: u32
+    buf:     ROBuffer,
+    line:    u32,
+    column:  u32,
+    defines: HashMap<Vec<u8>, Define>
 }
 
 impl Tokeniser {
-    pub fn new(expr: String) -> Self {
-        Self {
-            buf: ROBuffer::new(expr).unwrap(),
+    pub fn new(expr: String) -> Result<Self, CompileError> {
+        let (stripped, defines) = preprocessor::preprocess(&expr)?;
+        Ok(Self {
+            buf: ROBuffer::new(stripped)?,
             line: 1,
-            column: 1
-        }
+            column: 1,
+            defines
+        })
     }
 
     fn get_num(&mut self) -> Token {
@@ -153,7 +169,7 @@ impl Tokeniser {
         Token::new(TokenKind::Num(n), saved_col, saved_row)
     }
 
-    fn get_ident(&mut self) -> Token {
+    fn get_ident(&mut self) -> Result<Token, CompileError> {
         let mut bvec: Vec<u8> = Vec::new();
         let saved_col = self.column;
         let saved_row = self.line;
@@ -173,20 +189,58 @@ impl Tokeniser {
             self.column += 1;
         }
 
-        Token::new(TokenKind::Ident(bvec), saved_col, saved_row)
+        // Substitute #define values in place of the identifier, the same
+        // way the B-language preprocessor splices constants in. Only the
+        // remainder of the input *after* the directive is substituted, so
+        // a use on or before the definition's own line is left as an
+        // ordinary identifier instead.
+        // `preprocess` already guarantees every stored value is a single
+        // (optionally signed) integer literal; one that does not fit in an
+        // i64 is reported as `DefineOverflow`, so a forward substitution
+        // yields a `Num` token or an error, never a bogus identifier.
+        if let Some((value, def_line)) = self.defines.get(&bvec) {
+            if saved_row > *def_line {
+                let text = String::from_utf8(value.clone()).unwrap();
+                let n: i64 = text.parse().map_err(|_| CompileError::DefineOverflow {
+                    name: String::from_utf8_lossy(&bvec).into_owned(),
+                    line: saved_row, col: saved_col
+                })?;
+                return Ok(Token::new(TokenKind::Num(n), saved_col, saved_row));
+            }
+        }
+
+        Ok(Token::new(TokenKind::Ident(bvec), saved_col, saved_row))
+    }
+
+    // Peeks one byte past '=', '!', '<' or '>' to decide between the
+    // single-character operator and its '=' suffixed two-character form.
+    fn maybe_eq(&mut self, one: TokenKind, two: TokenKind) -> Token {
+        match self.buf.next() {
+            Some(b'=') => {
+                self.column += 1;
+                Token::new(two, self.column - 1, self.line)
+            }
+
+            Some(_) => {
+                self.buf.rewind();
+                Token::new(one, self.column, self.line)
+            }
+
+            None => Token::new(one, self.column, self.line)
+        }
     }
 
-    pub fn tokenise(&mut self) -> Token {
+    pub fn tokenise(&mut self) -> Result<Token, CompileError> {
         loop {
             let ch = self.buf.next();
-            if ch.is_none() { 
+            if ch.is_none() {
                 break;
             }
 
             let ret = match ch.unwrap() {
                 b'a'..=b'z' | b'A'..=b'Z' => {
                     self.buf.rewind();
-                    self.get_ident()
+                    self.get_ident()?
                 }
 
                 b'0'..=b'9' => {
@@ -205,7 +259,10 @@ impl Tokeniser {
                     continue;
                 }
 
-                b'='  => Token::new(TokenKind::Assign, self.column, self.line),
+                b'='  => self.maybe_eq(TokenKind::Assign, TokenKind::Eq),
+                b'!'  => self.maybe_eq(TokenKind::Invalid(b'!'), TokenKind::Ne),
+                b'<'  => self.maybe_eq(TokenKind::Lt, TokenKind::Le),
+                b'>'  => self.maybe_eq(TokenKind::Gt, TokenKind::Ge),
                 b'+'  => Token::new(TokenKind::Add, self.column, self.line),
                 b'-'  => Token::new(TokenKind::Sub, self.column, self.line),
                 b'*'  => Token::new(TokenKind::Mul, self.column, self.line),
@@ -222,21 +279,21 @@ impl Tokeniser {
 
             self.column += 1;
 
-            return ret;
+            return Ok(ret);
         }
 
-        Token::new(TokenKind::End, self.column, self.line)
+        Ok(Token::new(TokenKind::End, self.column, self.line))
     }
 
-    pub fn collect(&mut self) -> Result<Vec<Token>, ()> {
+    pub fn collect(&mut self) -> Result<Vec<Token>, CompileError> {
         let mut exprs: Vec<Token> = Vec::new();
         loop {
-            let t = self.tokenise();
+            let t = self.tokenise()?;
             match t.kind() {
                 TokenKind::Invalid(x) => {
-                    println!("Invalid token {} at line {} column {}",
-                        (*x as char), t.line(), t.col());
-                    return Err(());
+                    return Err(CompileError::InvalidToken {
+                        token: *x, line: t.line(), col: t.col()
+                    });
                 },
 
                 TokenKind::End => break,